@@ -0,0 +1,131 @@
+//! `std::io::Read`/`Write` streaming adapters for [`Source`](crate::Source)
+//! and [`Sink`](crate::Sink), so a QOI stream can be decoded from (or
+//! encoded to) a file or socket in bounded memory instead of first being
+//! materialized as a `Vec`.
+
+use std::io::{ErrorKind, Read, Write};
+
+use crate::{IOError, Rgba, Sink};
+
+const BUFFER_SIZE: usize = 4096;
+
+/// Adapts a [`Read`] into a byte [`Source`], buffering reads internally.
+///
+/// Decoding treats a [`ReadSource`] exactly like any other byte source: a
+/// clean end of stream yields `None` just like it would from an exhausted
+/// iterator. If the underlying reader instead failed, [`ReadSource::take_error`]
+/// recovers the `io::Error` that caused the `None`, so callers can tell the
+/// two apart after a decode `Result` comes back short.
+pub struct ReadSource<R> {
+    reader: R,
+    buffer: [u8; BUFFER_SIZE],
+    pos: usize,
+    filled: usize,
+    error: Option<std::io::Error>,
+}
+
+impl<R: Read> ReadSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: [0; BUFFER_SIZE],
+            pos: 0,
+            filled: 0,
+            error: None,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Takes the IO error that caused the source to stop yielding bytes,
+    /// if it stopped because of one rather than a clean end of stream.
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    fn refill(&mut self) -> bool {
+        loop {
+            match self.reader.read(&mut self.buffer) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.pos = 0;
+                    self.filled = n;
+                    return true;
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.error = Some(e);
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReadSource<R> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pos >= self.filled && !self.refill() {
+            return None;
+        }
+        let byte = self.buffer[self.pos];
+        self.pos += 1;
+        Some(byte)
+    }
+}
+
+/// Adapts a [`Write`] into a [`Sink`], flushing decoded output to it as it
+/// arrives instead of buffering the whole image in memory.
+pub struct WriteSink<W> {
+    writer: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> WriteSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Takes the IO error that caused writes to stop landing, if any.
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            if let Err(e) = self.writer.write_all(bytes) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+impl<W: Write> Sink<u8> for WriteSink<W> {
+    fn push(&mut self, thing: u8) {
+        self.write(&[thing]);
+    }
+
+    fn error(&self) -> Option<&dyn IOError> {
+        self.error.as_ref().map(|e| e as &dyn IOError)
+    }
+}
+
+impl<W: Write> Sink<Rgba> for WriteSink<W> {
+    fn push(&mut self, thing: Rgba) {
+        self.write(&[thing.red, thing.green, thing.blue, thing.alpha]);
+    }
+
+    fn error(&self) -> Option<&dyn IOError> {
+        self.error.as_ref().map(|e| e as &dyn IOError)
+    }
+}
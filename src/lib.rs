@@ -1,17 +1,34 @@
-use crate::Channels::Rgba;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use core::fmt::{Debug, Display};
+
+mod slice;
+pub use slice::decode_slice;
+
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+pub use io::{ReadSource, WriteSink};
+
 use crate::DecodeError::{OutOfBytes, UnknownTag};
 
-#[derive(Clone, Copy, Debug)]
-struct Rgba {
-    red: u8,
-    green: u8,
-    blue: u8,
-    alpha: u8,
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgba {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
 }
 
 #[derive(Clone, Copy)]
 #[repr(u8)]
-enum Channels {
+pub enum Channels {
     Rgb = 3,
     Rgba = 4,
 }
@@ -31,7 +48,7 @@ impl TryFrom<u8> for Channels {
 
 #[derive(Clone, Copy)]
 #[repr(u8)]
-enum ColorSpace {
+pub enum ColorSpace {
     SRgbWithLinearAlpha = 0,
     FullLinear = 1,
 }
@@ -48,22 +65,34 @@ impl TryFrom<u8> for ColorSpace {
         }
     }
 }
-struct Header {
-    width: u32,
-    height: u32,
-    channels: Channels,
-    colorspace: ColorSpace,
+pub struct Header {
+    pub width: u32,
+    pub height: u32,
+    pub channels: Channels,
+    pub colorspace: ColorSpace,
 }
 
 const MAGIC_STRING: &str = "qoif";
 
-trait Source: Iterator<Item = u8> {}
+pub trait Source<T = u8>: Iterator<Item = T> {}
+
+impl<T, I: Iterator<Item = T>> Source<T> for I {}
 
 impl Header {
     fn decode<S: Source>(source: &mut S) -> Option<Header> {
         if MAGIC_STRING.bytes().eq(source.take(4)) {
-            let width = u32::from_be_bytes([source.next()?; 4]);
-            let height = u32::from_be_bytes([source.next()?; 4]);
+            let width = u32::from_be_bytes([
+                source.next()?,
+                source.next()?,
+                source.next()?,
+                source.next()?,
+            ]);
+            let height = u32::from_be_bytes([
+                source.next()?,
+                source.next()?,
+                source.next()?,
+                source.next()?,
+            ]);
             let channels: Channels = source.next()?.try_into().ok()?;
             let colorspace: ColorSpace = source.next()?.try_into().ok()?;
             Some(Header {
@@ -87,17 +116,17 @@ impl Header {
             (self.width >> 16) as u8,
             (self.width >> 8) as u8,
             self.width as u8,
-            (self.height >> 24 ^ 0xff) as u8,
-            (self.height >> 16 ^ 0xff) as u8,
-            (self.height >> 8 ^ 0xff) as u8,
-            (self.height ^ 0xff) as u8,
+            (self.height >> 24) as u8,
+            (self.height >> 16) as u8,
+            (self.height >> 8) as u8,
+            self.height as u8,
             self.channels as u8,
             self.colorspace as u8,
         ]
     }
 }
 
-impl std::default::Default for Header {
+impl core::default::Default for Header {
     fn default() -> Self {
         Self {
             width: 0,
@@ -162,12 +191,21 @@ impl Default for Rgba {
 }
 
 /// Essentially just an output iterator..
-trait Sink<T> {
+pub trait Sink<T> {
     fn push(&mut self, thing: T);
 
-    fn reserve(&mut self, size: usize) {}
+    fn reserve(&mut self, _size: usize) {}
+
+    /// Returns the error that made a previous `push` fail to actually
+    /// deliver its data, if any. `push` itself has no `Result` to report
+    /// through, so sinks that can fail (like [`WriteSink`](crate::WriteSink))
+    /// stash it here for decode routines to check before reporting success.
+    fn error(&self) -> Option<&dyn IOError> {
+        None
+    }
 }
 
+#[cfg(feature = "alloc")]
 impl<T> Sink<T> for Vec<T> {
     fn push(&mut self, thing: T) {
         self.push(thing)
@@ -178,33 +216,73 @@ impl<T> Sink<T> for Vec<T> {
     }
 }
 
-struct Data {
+pub struct Data {
     last_seen_pixel: Rgba,
     stored_pixels: [Rgba; 64],
 }
 
-type Decoder = Data;
-type Encoder = Data;
+pub type Decoder = Data;
+pub type Encoder = Data;
 
-enum DecodeError {
+#[derive(Debug)]
+pub enum DecodeError {
     MissingTerminator,
     Header,
     IllegalRun,
     ConsecutiveIndex,
     OutOfBytes,
     UnknownTag,
+    SinkFailed,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let message = match self {
+            DecodeError::MissingTerminator => "stream is missing the QOI end-of-stream marker",
+            DecodeError::Header => "stream does not start with a valid QOI header",
+            DecodeError::IllegalRun => "run-length op would overshoot the declared pixel count",
+            DecodeError::ConsecutiveIndex => "illegal consecutive index op",
+            DecodeError::OutOfBytes => {
+                "stream ended before the current op was fully read, or the output slice is too short"
+            }
+            DecodeError::UnknownTag => "encountered an unrecognised op tag byte",
+            DecodeError::SinkFailed => "a previous write to the output sink failed",
+        };
+        f.write_str(message)
+    }
+}
+
+impl IOError for DecodeError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+#[cfg(feature = "std")]
+impl IOError for std::io::Error {}
+
+/// A minimal error-reporting bound that stands in for `std::error::Error`
+/// so callers can report decode failures without `std`.
+pub trait IOError: Debug + Display {}
+
+/// Formats any [`IOError`] as a `Display`, generic over the concrete error
+/// type rather than requiring `std::error::Error` — this is what lets
+/// error reporting work the same under `no_std`.
+pub fn report<E: IOError>(error: &E) -> &dyn Display {
+    error
 }
 
 impl Data {
     pub fn new() -> Self {
         Data {
-            last_seen_pixel: Rgba::zero(),
+            // The QOI spec fixes the initial previous pixel at {0,0,0,255},
+            // not all-zero, so a fresh encoder/decoder starts in sync.
+            last_seen_pixel: Rgba::new(),
             stored_pixels: [Rgba::zero(); 64],
         }
     }
 
     pub fn reset(&mut self) {
-        std::mem::swap(self, &mut Default::default());
+        *self = Default::default();
     }
 
     pub fn decode_into<So: Source, Si: Sink<Rgba>>(
@@ -212,77 +290,232 @@ impl Data {
         source: &mut So,
         sink: &mut Si,
     ) -> Result<Header, DecodeError> {
-        if let Some(header) = Header::decode(source) {
-            sink.reserve(header.height as usize * header.width as usize);
-            while let Some(byte) = source.next() {
-                match byte {
-                    0b1111_1110 => {
-                        if let Some(value) =
-                            Rgba::decode_with_alpha(source, self.last_seen_pixel.alpha)
-                        {
-                            self.last_seen_pixel = value;
-                            sink.push(self.last_seen_pixel);
-                        } else {
-                            return Err(OutOfBytes);
-                        }
-                    }
-                    0b1111_1111 => {
-                        if let Some(value) = Rgba::decode(source) {
-                            self.last_seen_pixel = value;
-                            sink.push(self.last_seen_pixel);
-                        } else {
-                            return Err(OutOfBytes);
-                        }
-                    }
-                    byte if byte & 0b1100_0000 == 0b0000_0000 => {
-                        self.last_seen_pixel = self.stored_pixels[byte as usize];
-                        sink.push(self.last_seen_pixel);
+        let header = Header::decode(source).ok_or(DecodeError::Header)?;
+        let pixel_count = header.width as usize * header.height as usize;
+        sink.reserve(pixel_count);
+
+        self.decode_pixels(source, pixel_count, |pixel| sink.push(pixel))?;
+        Self::read_terminator(source)?;
+        if sink.error().is_some() {
+            return Err(DecodeError::SinkFailed);
+        }
+
+        Ok(header)
+    }
+
+    /// Like [`Data::decode_into`], but writes raw pixel bytes into a byte
+    /// sink using `channels`, independently of what the header declares.
+    pub fn decode_into_channels<So: Source, Si: Sink<u8>>(
+        &mut self,
+        source: &mut So,
+        sink: &mut Si,
+        channels: Channels,
+    ) -> Result<Header, DecodeError> {
+        let header = Header::decode(source).ok_or(DecodeError::Header)?;
+        let pixel_count = header.width as usize * header.height as usize;
+        sink.reserve(pixel_count * channels as usize);
+
+        let source_channels = header.channels;
+        self.decode_pixels(source, pixel_count, |pixel| {
+            sink.push(pixel.red);
+            sink.push(pixel.green);
+            sink.push(pixel.blue);
+            if let Channels::Rgba = channels {
+                let alpha = match source_channels {
+                    Channels::Rgba => pixel.alpha,
+                    Channels::Rgb => 0xff,
+                };
+                sink.push(alpha);
+            }
+        })?;
+        Self::read_terminator(source)?;
+        if sink.error().is_some() {
+            return Err(DecodeError::SinkFailed);
+        }
+
+        Ok(header)
+    }
+
+    /// Walks the op stream, invoking `emit` once per decoded pixel, until
+    /// exactly `pixel_count` pixels have been produced. Leaves the 8-byte
+    /// end-of-stream marker for the caller to consume.
+    fn decode_pixels<So: Source>(
+        &mut self,
+        source: &mut So,
+        pixel_count: usize,
+        mut emit: impl FnMut(Rgba),
+    ) -> Result<(), DecodeError> {
+        let mut produced = 0usize;
+        while produced < pixel_count {
+            let byte = source.next().ok_or(OutOfBytes)?;
+            match byte {
+                0b1111_1110 => {
+                    let value = Rgba::decode_with_alpha(source, self.last_seen_pixel.alpha)
+                        .ok_or(OutOfBytes)?;
+                    self.stored_pixels[value.hash_index() as usize] = value;
+                    self.last_seen_pixel = value;
+                    emit(value);
+                    produced += 1;
+                }
+                0b1111_1111 => {
+                    let value = Rgba::decode(source).ok_or(OutOfBytes)?;
+                    self.stored_pixels[value.hash_index() as usize] = value;
+                    self.last_seen_pixel = value;
+                    emit(value);
+                    produced += 1;
+                }
+                byte if byte & 0b1100_0000 == 0b0000_0000 => {
+                    self.last_seen_pixel = self.stored_pixels[byte as usize];
+                    emit(self.last_seen_pixel);
+                    produced += 1;
+                }
+                byte if byte & 0b1100_0000 == 0b0100_0000 => {
+                    fn shift(old_value: u8, read_byte: u8, shift: u8) -> u8 {
+                        old_value.wrapping_add(((read_byte >> shift) & 0b11).wrapping_sub(2))
                     }
-                    byte if byte & 0b1100_0000 == 0b0100_0000 => {
-                        fn shift(old_value: u8, read_byte: u8, shift: u8) -> u8 {
-                            old_value.wrapping_add((read_byte >> shift ^ 0b11).wrapping_sub(2))
-                        }
-                        self.last_seen_pixel = Rgba {
-                            red: shift(self.last_seen_pixel.red, byte, 4),
-                            green: shift(self.last_seen_pixel.green, byte, 2),
-                            blue: shift(self.last_seen_pixel.blue, byte, 0),
-                            alpha: self.last_seen_pixel.alpha,
-                        };
-                        sink.push(self.last_seen_pixel);
+                    self.last_seen_pixel = Rgba {
+                        red: shift(self.last_seen_pixel.red, byte, 4),
+                        green: shift(self.last_seen_pixel.green, byte, 2),
+                        blue: shift(self.last_seen_pixel.blue, byte, 0),
+                        alpha: self.last_seen_pixel.alpha,
+                    };
+                    self.stored_pixels[self.last_seen_pixel.hash_index() as usize] =
+                        self.last_seen_pixel;
+                    emit(self.last_seen_pixel);
+                    produced += 1;
+                }
+                byte if byte & 0b1100_0000 == 0b1000_0000 => {
+                    let second_byte = source.next().ok_or(OutOfBytes)?;
+                    // green bias is 32
+                    let green_diff = (byte & 0b0011_1111).wrapping_sub(32);
+
+                    // red and blue bias is 8
+                    let red_diff = (second_byte >> 4 & 0b1111)
+                        .wrapping_sub(8)
+                        .wrapping_add(green_diff);
+                    let blue_diff = (second_byte & 0b1111)
+                        .wrapping_sub(8)
+                        .wrapping_add(green_diff);
+                    self.last_seen_pixel = Rgba {
+                        red: self.last_seen_pixel.red.wrapping_add(red_diff),
+                        green: self.last_seen_pixel.green.wrapping_add(green_diff),
+                        blue: self.last_seen_pixel.blue.wrapping_add(blue_diff),
+                        alpha: self.last_seen_pixel.alpha,
+                    };
+                    self.stored_pixels[self.last_seen_pixel.hash_index() as usize] =
+                        self.last_seen_pixel;
+                    emit(self.last_seen_pixel);
+                    produced += 1;
+                }
+                byte if byte & 0b1100_0000 == 0b1100_0000 => {
+                    let run = (byte & 0b0011_1111) as usize + 1;
+                    if produced + run > pixel_count {
+                        return Err(DecodeError::IllegalRun);
                     }
-                    byte if byte & 0b1100_0000 == 0b1000_0000 => {
-                        if let Some(second_byte) = source.next() {
-                            // green bias is 32
-                            let green_diff = (byte & 0x0011_1111).wrapping_sub(32);
-
-                            // red and blue bias is 8
-                            let red_diff = (second_byte >> 4 & 0x1111)
-                                .wrapping_sub(8)
-                                .wrapping_add(green_diff);
-                            let blue_diff = (second_byte & 0x1111)
-                                .wrapping_sub(8)
-                                .wrapping_add(green_diff);
-                            self.last_seen_pixel = Rgba {
-                                red: self.last_seen_pixel.red + red_diff,
-                                green: self.last_seen_pixel.green + green_diff,
-                                blue: self.last_seen_pixel.blue + blue_diff,
-                                alpha: self.last_seen_pixel.alpha,
-                            };
-                            sink.push(self.last_seen_pixel);
-                        }
+                    for _ in 0..run {
+                        emit(self.last_seen_pixel);
                     }
-                    byte if byte & 0b1100_0000 == 0b1100_0000 => {
-                        for _ in 0..(byte & 0b0011_1111) {
-                            sink.push(self.last_seen_pixel);
-                        }
+                    produced += run;
+                }
+                _ => return Err(UnknownTag),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_terminator<So: Source>(source: &mut So) -> Result<(), DecodeError> {
+        let mut terminator = [0u8; 8];
+        for slot in terminator.iter_mut() {
+            *slot = source.next().ok_or(DecodeError::MissingTerminator)?;
+        }
+        if terminator != [0, 0, 0, 0, 0, 0, 0, 1] {
+            return Err(DecodeError::MissingTerminator);
+        }
+        Ok(())
+    }
+
+    pub fn encode_into<So: Source<Rgba>, Si: Sink<u8>>(
+        &mut self,
+        source: &mut So,
+        sink: &mut Si,
+        width: u32,
+        height: u32,
+        channels: Channels,
+    ) -> Header {
+        let header = Header {
+            width,
+            height,
+            channels,
+            colorspace: ColorSpace::SRgbWithLinearAlpha,
+        };
+        for byte in header.encode() {
+            sink.push(byte);
+        }
+
+        let mut run = 0u8;
+        for pixel in source {
+            if pixel == self.last_seen_pixel {
+                run += 1;
+                if run == 62 {
+                    sink.push(0b1100_0000 | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if run > 0 {
+                sink.push(0b1100_0000 | (run - 1));
+                run = 0;
+            }
+
+            let hash = pixel.hash_index();
+            if self.stored_pixels[hash as usize] == pixel {
+                sink.push(hash);
+            } else if pixel.alpha == self.last_seen_pixel.alpha {
+                let dr = pixel.red.wrapping_sub(self.last_seen_pixel.red) as i8;
+                let dg = pixel.green.wrapping_sub(self.last_seen_pixel.green) as i8;
+                let db = pixel.blue.wrapping_sub(self.last_seen_pixel.blue) as i8;
+
+                if (-2..=1).contains(&dr) && (-2..=1).contains(&dg) && (-2..=1).contains(&db) {
+                    sink.push(
+                        0b0100_0000
+                            | ((dr + 2) as u8) << 4
+                            | ((dg + 2) as u8) << 2
+                            | (db + 2) as u8,
+                    );
+                } else {
+                    let dr_dg = dr.wrapping_sub(dg);
+                    let db_dg = db.wrapping_sub(dg);
+                    if (-32..=31).contains(&dg) && (-8..=7).contains(&dr_dg) && (-8..=7).contains(&db_dg)
+                    {
+                        sink.push(0b1000_0000 | (dg + 32) as u8);
+                        sink.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                    } else {
+                        sink.push(0b1111_1110);
+                        sink.push(pixel.red);
+                        sink.push(pixel.green);
+                        sink.push(pixel.blue);
                     }
-                    _ => return Err(UnknownTag),
                 }
+            } else {
+                sink.push(0b1111_1111);
+                sink.push(pixel.red);
+                sink.push(pixel.green);
+                sink.push(pixel.blue);
+                sink.push(pixel.alpha);
             }
-            Ok(header)
-        } else {
-            Err(DecodeError::Header)
+
+            self.stored_pixels[hash as usize] = pixel;
+            self.last_seen_pixel = pixel;
+        }
+        if run > 0 {
+            sink.push(0b1100_0000 | (run - 1));
         }
+
+        for byte in [0, 0, 0, 0, 0, 0, 0, 1] {
+            sink.push(byte);
+        }
+
+        header
     }
 }
 
@@ -294,9 +527,264 @@ impl Default for Data {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    use super::*;
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::{format, vec};
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn encode_then_decode_round_trips() {
+        let pixels = [
+            Rgba {
+                red: 10,
+                green: 20,
+                blue: 30,
+                alpha: 255,
+            },
+            Rgba {
+                red: 10,
+                green: 20,
+                blue: 30,
+                alpha: 255,
+            },
+            Rgba {
+                red: 0,
+                green: 0,
+                blue: 0,
+                alpha: 128,
+            },
+            Rgba {
+                red: 200,
+                green: 5,
+                blue: 5,
+                alpha: 255,
+            },
+        ];
+
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        let encoded_header = encoder.encode_into(
+            &mut pixels.into_iter(),
+            &mut encoded,
+            2,
+            2,
+            Channels::Rgba,
+        );
+
+        let mut decoded = Vec::new();
+        let mut decoder = Data::new();
+        let header = decoder
+            .decode_into(&mut encoded.into_iter(), &mut decoded)
+            .unwrap();
+
+        assert_eq!(header.width, encoded_header.width);
+        assert_eq!(header.height, encoded_header.height);
+        assert_eq!(decoded, pixels);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn decode_rejects_missing_terminator() {
+        let pixels = [Rgba::new(); 4];
+
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        encoder.encode_into(&mut pixels.into_iter(), &mut encoded, 2, 2, Channels::Rgba);
+
+        // Drop the 8-byte end-of-stream marker the encoder just appended.
+        encoded.truncate(encoded.len() - 8);
+
+        let mut decoded = Vec::new();
+        let mut decoder = Data::new();
+        let result = decoder.decode_into(&mut encoded.into_iter(), &mut decoded);
+
+        assert!(matches!(result, Err(DecodeError::MissingTerminator)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn decode_into_channels_rgb_drops_alpha() {
+        let pixels = [
+            Rgba {
+                red: 1,
+                green: 2,
+                blue: 3,
+                alpha: 200,
+            },
+            Rgba {
+                red: 4,
+                green: 5,
+                blue: 6,
+                alpha: 100,
+            },
+        ];
+
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        encoder.encode_into(&mut pixels.into_iter(), &mut encoded, 2, 1, Channels::Rgba);
+
+        let mut out = Vec::new();
+        let mut decoder = Data::new();
+        decoder
+            .decode_into_channels(&mut encoded.into_iter(), &mut out, Channels::Rgb)
+            .unwrap();
+
+        assert_eq!(out, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn decode_into_channels_rgba_fills_opaque_alpha_for_rgb_source() {
+        let pixels = [Rgba {
+            red: 9,
+            green: 8,
+            blue: 7,
+            alpha: 255,
+        }];
+
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        encoder.encode_into(&mut pixels.into_iter(), &mut encoded, 1, 1, Channels::Rgb);
+
+        let mut out = Vec::new();
+        let mut decoder = Data::new();
+        decoder
+            .decode_into_channels(&mut encoded.into_iter(), &mut out, Channels::Rgba)
+            .unwrap();
+
+        assert_eq!(out, vec![9, 8, 7, 0xff]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn report_formats_any_ioerror_by_display() {
+        let err = DecodeError::UnknownTag;
+        assert_eq!(
+            format!("{}", report(&err)),
+            "encountered an unrecognised op tag byte"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn decode_slice_matches_iterator_decode() {
+        let pixels = [
+            Rgba {
+                red: 10,
+                green: 20,
+                blue: 30,
+                alpha: 255,
+            },
+            Rgba {
+                red: 10,
+                green: 20,
+                blue: 30,
+                alpha: 255,
+            },
+            Rgba {
+                red: 0,
+                green: 0,
+                blue: 0,
+                alpha: 128,
+            },
+            Rgba {
+                red: 200,
+                green: 5,
+                blue: 5,
+                alpha: 255,
+            },
+        ];
+
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        encoder.encode_into(&mut pixels.into_iter(), &mut encoded, 2, 2, Channels::Rgba);
+
+        let mut via_slice = [0u8; 4 * 4];
+        let written = decode_slice::<4>(&encoded, &mut via_slice).unwrap();
+
+        let expected: Vec<u8> = pixels
+            .iter()
+            .flat_map(|p| [p.red, p.green, p.blue, p.alpha])
+            .collect();
+        assert_eq!(written, expected.len());
+        assert_eq!(via_slice.to_vec(), expected);
+
+        let mut via_iter = Vec::new();
+        Data::new()
+            .decode_into_channels(&mut encoded.iter().copied(), &mut via_iter, Channels::Rgba)
+            .unwrap();
+        assert_eq!(via_slice.to_vec(), via_iter);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_source_and_write_sink_round_trip_through_std_io() {
+        let pixels = [
+            Rgba {
+                red: 1,
+                green: 1,
+                blue: 1,
+                alpha: 255,
+            },
+            Rgba {
+                red: 2,
+                green: 2,
+                blue: 2,
+                alpha: 255,
+            },
+        ];
+
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        encoder.encode_into(&mut pixels.into_iter(), &mut encoded, 2, 1, Channels::Rgba);
+
+        let mut read_source = ReadSource::new(&encoded[..]);
+        let mut decoded = Vec::new();
+        let mut write_sink = WriteSink::new(&mut decoded);
+        let mut decoder = Data::new();
+        decoder
+            .decode_into_channels(&mut read_source, &mut write_sink, Channels::Rgba)
+            .unwrap();
+
+        let expected: Vec<u8> = pixels
+            .iter()
+            .flat_map(|p| [p.red, p.green, p.blue, p.alpha])
+            .collect();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn decode_into_channels_reports_sink_failed_when_writes_fail() {
+        struct AlwaysBrokenPipe;
+
+        impl std::io::Write for AlwaysBrokenPipe {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let pixels = [Rgba::new(); 4];
+        let mut encoded = Vec::new();
+        let mut encoder = Data::new();
+        encoder.encode_into(&mut pixels.into_iter(), &mut encoded, 2, 2, Channels::Rgba);
+
+        let mut write_sink = WriteSink::new(AlwaysBrokenPipe);
+        let mut decoder = Data::new();
+        let result =
+            decoder.decode_into_channels(&mut encoded.into_iter(), &mut write_sink, Channels::Rgba);
+
+        assert!(matches!(result, Err(DecodeError::SinkFailed)));
+    }
 }
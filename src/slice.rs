@@ -0,0 +1,171 @@
+//! A slice-oriented decode fast path for the common `&[u8] -> &mut [u8]`
+//! case, trading the byte-at-a-time [`Source`](crate::Source)-driven loop
+//! in [`Data::decode_into`](crate::Data) for direct slice pattern matching
+//! and whole-pixel stores.
+
+use crate::{ColorSpace, Rgba};
+
+/// Decodes a QOI-encoded `data` buffer into `out`, writing `N`-channel
+/// pixels (`N` is 3 for RGB or 4 for RGBA) and returning the number of
+/// bytes written.
+///
+/// Unlike [`Data::decode_into`](crate::Data), this never goes through a
+/// [`Source`](crate::Source) iterator: it matches directly on the input
+/// slice and writes each decoded pixel as a single array store into `out`,
+/// reinterpreted via `bytemuck` as `&mut [[u8; N]]`.
+///
+/// `N` must be 3 (RGB) or 4 (RGBA); any other value is a build error.
+pub fn decode_slice<const N: usize>(
+    data: &[u8],
+    out: &mut [u8],
+) -> Result<usize, crate::DecodeError>
+where
+    [u8; N]: bytemuck::Pod,
+{
+    const { assert!(N == 3 || N == 4, "decode_slice only supports N = 3 (RGB) or N = 4 (RGBA)") };
+
+    if N == 4 {
+        decode_slice_impl::<N, true>(data, out)
+    } else {
+        decode_slice_impl::<N, false>(data, out)
+    }
+}
+
+fn decode_slice_impl<const N: usize, const RGBA: bool>(
+    data: &[u8],
+    out: &mut [u8],
+) -> Result<usize, crate::DecodeError>
+where
+    [u8; N]: bytemuck::Pod,
+{
+    use crate::DecodeError::{Header, IllegalRun, MissingTerminator, OutOfBytes, UnknownTag};
+
+    let (header, mut rest) = match data {
+        [b'q', b'o', b'i', b'f', w0, w1, w2, w3, h0, h1, h2, h3, channels, colorspace, rest @ ..] => {
+            let width = u32::from_be_bytes([*w0, *w1, *w2, *w3]);
+            let height = u32::from_be_bytes([*h0, *h1, *h2, *h3]);
+            let channels = crate::Channels::try_from(*channels).map_err(|_| Header)?;
+            let colorspace = ColorSpace::try_from(*colorspace).map_err(|_| Header)?;
+            (
+                crate::Header {
+                    width,
+                    height,
+                    channels,
+                    colorspace,
+                },
+                rest,
+            )
+        }
+        _ => return Err(Header),
+    };
+
+    let pixel_count = header.width as usize * header.height as usize;
+    let out = out.get_mut(..pixel_count * N).ok_or(OutOfBytes)?;
+    let pixels: &mut [[u8; N]] = bytemuck::cast_slice_mut(out);
+
+    let mut index = [Rgba::zero(); 256];
+    // The QOI spec fixes the initial previous pixel at {0,0,0,255}.
+    let mut last_seen_pixel = Rgba::new();
+    let mut produced = 0usize;
+
+    while produced < pixel_count {
+        let (tag, next) = match rest {
+            [tag, next @ ..] => (*tag, next),
+            [] => return Err(OutOfBytes),
+        };
+        rest = next;
+
+        match tag {
+            0b1111_1110 => {
+                let [r, g, b, next @ ..] = rest else {
+                    return Err(OutOfBytes);
+                };
+                last_seen_pixel = Rgba {
+                    red: *r,
+                    green: *g,
+                    blue: *b,
+                    alpha: last_seen_pixel.alpha,
+                };
+                rest = next;
+            }
+            0b1111_1111 => {
+                let [r, g, b, a, next @ ..] = rest else {
+                    return Err(OutOfBytes);
+                };
+                last_seen_pixel = Rgba {
+                    red: *r,
+                    green: *g,
+                    blue: *b,
+                    alpha: *a,
+                };
+                rest = next;
+            }
+            tag if tag & 0b1100_0000 == 0b0000_0000 => {
+                last_seen_pixel = index[tag as usize];
+            }
+            tag if tag & 0b1100_0000 == 0b0100_0000 => {
+                fn shift(old_value: u8, tag: u8, shift: u8) -> u8 {
+                    old_value.wrapping_add(((tag >> shift) & 0b11).wrapping_sub(2))
+                }
+                last_seen_pixel = Rgba {
+                    red: shift(last_seen_pixel.red, tag, 4),
+                    green: shift(last_seen_pixel.green, tag, 2),
+                    blue: shift(last_seen_pixel.blue, tag, 0),
+                    alpha: last_seen_pixel.alpha,
+                };
+            }
+            tag if tag & 0b1100_0000 == 0b1000_0000 => {
+                let [second, next @ ..] = rest else {
+                    return Err(OutOfBytes);
+                };
+                rest = next;
+
+                // green bias is 32
+                let green_diff = (tag & 0b0011_1111).wrapping_sub(32);
+
+                // red and blue bias is 8
+                let red_diff = (second >> 4 & 0b1111)
+                    .wrapping_sub(8)
+                    .wrapping_add(green_diff);
+                let blue_diff = (second & 0b1111).wrapping_sub(8).wrapping_add(green_diff);
+                last_seen_pixel = Rgba {
+                    red: last_seen_pixel.red.wrapping_add(red_diff),
+                    green: last_seen_pixel.green.wrapping_add(green_diff),
+                    blue: last_seen_pixel.blue.wrapping_add(blue_diff),
+                    alpha: last_seen_pixel.alpha,
+                };
+            }
+            tag if tag & 0b1100_0000 == 0b1100_0000 => {
+                let run = (tag & 0b0011_1111) as usize + 1;
+                if produced + run > pixel_count {
+                    return Err(IllegalRun);
+                }
+                for pixel in &mut pixels[produced..produced + run] {
+                    write_pixel::<N, RGBA>(pixel, last_seen_pixel);
+                }
+                produced += run;
+                continue;
+            }
+            _ => return Err(UnknownTag),
+        }
+
+        index[last_seen_pixel.hash_index() as usize] = last_seen_pixel;
+        write_pixel::<N, RGBA>(&mut pixels[produced], last_seen_pixel);
+        produced += 1;
+    }
+
+    match rest {
+        [0, 0, 0, 0, 0, 0, 0, 1, ..] => Ok(pixel_count * N),
+        _ => Err(MissingTerminator),
+    }
+}
+
+#[inline]
+fn write_pixel<const N: usize, const RGBA: bool>(slot: &mut [u8; N], pixel: Rgba) {
+    slot[0] = pixel.red;
+    slot[1] = pixel.green;
+    slot[2] = pixel.blue;
+    if RGBA {
+        slot[3] = pixel.alpha;
+    }
+}